@@ -5,15 +5,49 @@
 //! - Lazily caches extracted text per page for repeated access
 //! - Validates cache entries by file mtime to detect stale data
 //! - Uses canonical paths to handle symlinks and relative paths
+//! - Bounds resident memory to a configurable byte budget, evicting the
+//!   least-recently-used entries with a CLOCK (second-chance) policy
+//! - Optionally zstd-compresses cached page text to shrink resident memory,
+//!   keeping a small hot set of decompressed pages for repeated access
+//! - Is tunable via a [`CacheConfig`] installed once at startup (byte budget,
+//!   an optional per-entry TTL, mmap vs. plain-read loading, eviction policy)
 
 use anyhow::{Context, Result};
 use lopdf::Document;
 use memmap2::Mmap;
 use papaya::HashMap;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, OnceLock};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default memory budget for the PDF cache: 512 MiB.
+const DEFAULT_BUDGET_BYTES: usize = 512 * 1024 * 1024;
+
+/// Number of most-recently-accessed pages per `CachedPdf` kept decompressed,
+/// so repeated reads of the same page (e.g. grep followed by a read) don't
+/// pay the decompression cost every time.
+const HOT_TEXT_CAPACITY: usize = 8;
+
+/// A page's entry in `text_cache`: either the plain extracted text, or a
+/// zstd-compressed blob plus its decompressed length (used for size
+/// accounting without having to decompress just to measure it).
+#[derive(Clone)]
+enum CachedText {
+    Plain(Arc<String>),
+    Compressed(Arc<[u8]>, usize),
+}
+
+impl CachedText {
+    fn resident_size(&self) -> usize {
+        match self {
+            CachedText::Plain(text) => text.len(),
+            CachedText::Compressed(blob, _) => blob.len(),
+        }
+    }
+}
 
 // ==============================================================================
 // Cached PDF Entry
@@ -28,14 +62,67 @@ use std::time::SystemTime;
 #[derive(Clone)]
 pub struct CachedPdf {
     doc: Arc<Document>,
+    /// Canonical path backing this entry, kept around so rendering (which
+    /// needs to reopen the file through a rasterizer rather than `doc`) can
+    /// be driven from the same cache entry used for text extraction.
+    path: Arc<PathBuf>,
     mtime: SystemTime,
-    /// Lazily cached extracted text per page (1-indexed).
-    text_cache: Arc<HashMap<u32, Arc<String>>>,
+    /// Lazily cached extracted text per page (1-indexed), compressed or
+    /// plain depending on `compression_enabled` at the time each page was
+    /// inserted.
+    text_cache: Arc<HashMap<u32, CachedText>>,
+    /// Small LRU of the most recently decompressed pages, so repeated
+    /// `page_text` calls for the same page don't re-decompress. Each entry
+    /// carries the number of bytes it holds *beyond* what `text_cache`
+    /// already accounts for: 0 when uncompressed (the `Arc<String>` here is
+    /// the same allocation referenced from `text_cache`'s `Plain` variant),
+    /// or the decompressed length when the source was compressed (a real
+    /// extra copy, since `text_cache` only holds the compressed blob).
+    hot_text: Arc<Mutex<VecDeque<(u32, Arc<String>, usize)>>>,
+    /// Shared with `PdfCache`: whether newly-inserted pages should be
+    /// zstd-compressed. Reading it per-insert (rather than baking the choice
+    /// in at load time) lets `PdfCache::set_text_compression` affect entries
+    /// already resident in the cache.
+    compression_enabled: Arc<AtomicBool>,
+    /// Lazily cached rendered pages, keyed by (page, dpi bits) since `f32`
+    /// doesn't implement `Eq`/`Hash`.
+    raster_cache: Arc<HashMap<(u32, u32), Arc<crate::pdf::render::PageImage>>>,
+    /// Approximate resident size in bytes: the file's byte length seen at
+    /// parse time (a proxy for the parsed `Document`) plus the resident size
+    /// of each page in `text_cache` (string length, or compressed blob
+    /// length), plus any extra decompressed bytes held in `hot_text` beyond
+    /// what `text_cache` already counts, plus the PNG bytes of every image in
+    /// `raster_cache`. Shared
+    /// with the cache's bookkeeping so `PdfCache`'s evictor can read it
+    /// without a separate lookup.
+    size: Arc<AtomicUsize>,
+    /// Set on every `document()`/`page_text()` access; cleared by the CLOCK
+    /// evictor to give a recently-used entry a second chance before evicting it.
+    referenced: Arc<AtomicBool>,
+    /// Updated on every access; consulted by `PdfCache::get` when a TTL is
+    /// configured, so entries idle past the TTL are reloaded even if the
+    /// backing file's mtime hasn't changed.
+    last_accessed: Arc<Mutex<Instant>>,
+    /// Cleared once this entry is no longer the one registered in
+    /// `PdfCache.cache`/`resident` - either evicted by `evict_to_budget` or
+    /// superseded by a stale-mtime reload. A caller may still be holding a
+    /// cloned `CachedPdf` past that point (e.g. mid-`grep_pdf`); further
+    /// growth/shrink on an orphaned clone must not reach `PdfCache.total_bytes`,
+    /// since there's no `resident` slot left to ever reclaim it from.
+    live: Arc<AtomicBool>,
 }
 
 impl CachedPdf {
+    /// Mark this entry as recently used: set the CLOCK reference bit and
+    /// reset the TTL clock.
+    fn touch(&self) {
+        self.referenced.store(true, Ordering::Relaxed);
+        *self.last_accessed.lock().unwrap() = Instant::now();
+    }
+
     /// Get a reference to the cached parsed document.
     pub fn document(&self) -> &Arc<Document> {
+        self.touch();
         &self.doc
     }
 
@@ -43,17 +130,195 @@ impl CachedPdf {
     ///
     /// The page number is 1-indexed.
     pub fn page_text(&self, page_num: u32) -> Result<Arc<String>, pdf_extract::OutputError> {
+        self.touch();
+
+        if let Some(text) = self.hot_text_get(page_num) {
+            return Ok(text);
+        }
+
         let guard = self.text_cache.pin();
-        if let Some(text) = guard.get(&page_num) {
-            return Ok(Arc::clone(text));
+        if let Some(entry) = guard.get(&page_num) {
+            let (text, hot_extra_bytes) = match entry {
+                CachedText::Plain(text) => (Arc::clone(text), 0),
+                CachedText::Compressed(blob, original_len) => {
+                    (Arc::new(decompress_text(blob, *original_len)), *original_len)
+                }
+            };
+            self.hot_text_insert(page_num, Arc::clone(&text), hot_extra_bytes);
+            return Ok(text);
+        }
+
+        // Extract the text, then store it compressed or plain depending on
+        // the cache's current setting.
+        let text = Arc::new(extract_text_from_doc_page(&self.doc, page_num)?);
+        let (entry, hot_extra_bytes) = if self.compression_enabled.load(Ordering::Relaxed) {
+            let original_len = text.len();
+            (
+                CachedText::Compressed(compress_text(text.as_str()), original_len),
+                original_len,
+            )
+        } else {
+            // Uncompressed: `text_cache` and `hot_text` share the same
+            // `Arc<String>` allocation, so the hot set adds no extra bytes.
+            (CachedText::Plain(Arc::clone(&text)), 0)
+        };
+        let size = entry.resident_size();
+        let was_vacant = guard.insert(page_num, entry).is_none();
+
+        self.hot_text_insert(page_num, Arc::clone(&text), hot_extra_bytes);
+
+        // Account the newly cached text against this entry's size and the
+        // cache's global budget, evicting older entries if we're now over -
+        // but only if this page wasn't already resident: concurrent cache
+        // misses on the same page (e.g. `extract_text_pages` fanning out
+        // over duplicate page numbers via rayon) can both extract and both
+        // reach this insert, and `text_cache` just overwrites one of them,
+        // so accounting both would double-count the same bytes.
+        if was_vacant {
+            self.account_growth(size);
         }
 
-        // Extract and cache the text.
-        let text = extract_text_from_doc_page(&self.doc, page_num)?;
-        let text = Arc::new(text);
-        guard.insert(page_num, Arc::clone(&text));
         Ok(text)
     }
+
+    /// Account newly-resident bytes against this entry's own `size`, and -
+    /// only while this entry is still `live` (i.e. still the one registered
+    /// in `PdfCache.cache`/`resident`) - against the cache's global budget,
+    /// evicting older entries if now over. Once `live` is cleared (evicted,
+    /// or superseded by a reload), further growth on a caller's orphaned
+    /// clone stays purely local instead of permanently inflating
+    /// `total_bytes` with no `resident` slot left to ever reclaim it from.
+    fn account_growth(&self, bytes: usize) {
+        self.size.fetch_add(bytes, Ordering::Relaxed);
+        if self.live.load(Ordering::Relaxed) {
+            cache().account_growth(bytes);
+        }
+    }
+
+    /// Release bytes that are no longer resident in this entry, the
+    /// `live`-gated counterpart to [`CachedPdf::account_growth`].
+    fn account_shrink(&self, bytes: usize) {
+        self.size.fetch_sub(bytes, Ordering::Relaxed);
+        if self.live.load(Ordering::Relaxed) {
+            cache().account_shrink(bytes);
+        }
+    }
+
+    /// Look up a page in the small decompressed hot set, moving it to the
+    /// back (most-recently-used) if found.
+    fn hot_text_get(&self, page_num: u32) -> Option<Arc<String>> {
+        let mut hot = self.hot_text.lock().unwrap();
+        if let Some(pos) = hot.iter().position(|(p, _, _)| *p == page_num) {
+            let (_, text, hot_extra_bytes) = hot.remove(pos).unwrap();
+            hot.push_back((page_num, Arc::clone(&text), hot_extra_bytes));
+            return Some(text);
+        }
+        None
+    }
+
+    /// Remember a decompressed page in the hot set, evicting the
+    /// least-recently-used entry once over `HOT_TEXT_CAPACITY`.
+    ///
+    /// `hot_extra_bytes` is the number of bytes this entry holds beyond what
+    /// `text_cache` already accounts for (see the `hot_text` field doc);
+    /// inserting/evicting keeps `size` and the cache's global `total_bytes`
+    /// in sync with it.
+    fn hot_text_insert(&self, page_num: u32, text: Arc<String>, hot_extra_bytes: usize) {
+        let mut hot = self.hot_text.lock().unwrap();
+        if let Some(pos) = hot.iter().position(|(p, _, _)| *p == page_num) {
+            let (_, _, old_extra_bytes) = hot.remove(pos).unwrap();
+            self.release_hot_bytes(old_extra_bytes);
+        }
+        hot.push_back((page_num, text, hot_extra_bytes));
+        self.account_hot_bytes(hot_extra_bytes);
+        while hot.len() > HOT_TEXT_CAPACITY {
+            let (_, _, evicted_extra_bytes) = hot.pop_front().unwrap();
+            self.release_hot_bytes(evicted_extra_bytes);
+        }
+    }
+
+    /// Account newly-resident `hot_text` bytes against this entry's size and
+    /// the cache's global budget, evicting older entries if now over budget.
+    fn account_hot_bytes(&self, bytes: usize) {
+        if bytes == 0 {
+            return;
+        }
+        self.account_growth(bytes);
+    }
+
+    /// Release `hot_text` bytes that are no longer resident (the hot set
+    /// dropped or refreshed an entry), without triggering an eviction pass.
+    fn release_hot_bytes(&self, bytes: usize) {
+        if bytes == 0 {
+            return;
+        }
+        self.account_shrink(bytes);
+    }
+
+    /// Get a rendered page image at the given DPI, using the raster cache
+    /// if available.
+    ///
+    /// The page number is 1-indexed; out-of-range pages return an error
+    /// rather than panicking, the same as `render::render_pages`'s own
+    /// validation.
+    pub fn page_image(
+        &self,
+        page_num: u32,
+        dpi: f32,
+    ) -> Result<Arc<crate::pdf::render::PageImage>> {
+        self.touch();
+
+        let total_pages = self.doc.get_pages().len() as u32;
+        if page_num == 0 || page_num > total_pages {
+            anyhow::bail!("Page {} is out of range (1-{})", page_num, total_pages);
+        }
+
+        if let Some(image) = self.cached_page_image(page_num, dpi) {
+            return Ok(image);
+        }
+
+        let image = Arc::new(crate::pdf::render::render_page(&self.path, page_num, dpi)?);
+        self.insert_page_image(page_num, dpi, Arc::clone(&image));
+
+        Ok(image)
+    }
+
+    /// Look up a rendered page in the raster cache without rendering on a
+    /// miss. Used by `render::render_pages` to batch cache-miss pages
+    /// through a single pdfium document open instead of one per page.
+    pub(crate) fn cached_page_image(
+        &self,
+        page_num: u32,
+        dpi: f32,
+    ) -> Option<Arc<crate::pdf::render::PageImage>> {
+        self.touch();
+        let key = (page_num, dpi.to_bits());
+        self.raster_cache.pin().get(&key).cloned()
+    }
+
+    /// Insert a freshly-rendered page image into the raster cache,
+    /// accounting its bytes against this entry's size and the cache's
+    /// global budget the same way a `page_image` cache miss does.
+    ///
+    /// Only accounts growth if this `(page, dpi)` wasn't already resident:
+    /// `render_pages` collects every cache-miss page before rendering or
+    /// inserting any of them, so a caller that asks for the same page twice
+    /// in one batch (or races another caller rendering the same page) would
+    /// otherwise insert the same bytes twice and double-count them against `size`/`total_bytes`
+    /// even though the map itself just overwrites one entry.
+    pub(crate) fn insert_page_image(
+        &self,
+        page_num: u32,
+        dpi: f32,
+        image: Arc<crate::pdf::render::PageImage>,
+    ) {
+        let key = (page_num, dpi.to_bits());
+        let bytes = image.png.len();
+        let guard = self.raster_cache.pin();
+        if guard.insert(key, image).is_none() {
+            self.account_growth(bytes);
+        }
+    }
 }
 
 /// Extract text from a single page using pdf-extract's output_doc_page.
@@ -67,6 +332,136 @@ fn extract_text_from_doc_page(
     Ok(text)
 }
 
+/// Compress extracted page text for storage in `text_cache`.
+fn compress_text(text: &str) -> Arc<[u8]> {
+    // Level 3 favors extraction speed over ratio, matching the per-page
+    // access pattern (many small blobs, decompressed individually).
+    zstd::stream::encode_all(text.as_bytes(), 3)
+        .expect("zstd compression of page text should not fail")
+        .into()
+}
+
+/// Decompress a page text blob previously produced by `compress_text`.
+fn decompress_text(blob: &[u8], original_len: usize) -> String {
+    let bytes = zstd::stream::decode_all(blob).expect("zstd decompression of page text blob");
+    debug_assert_eq!(bytes.len(), original_len);
+    String::from_utf8(bytes).expect("compressed page text should round-trip as valid UTF-8")
+}
+
+// ==============================================================================
+// Cache Configuration
+// ==============================================================================
+
+/// Eviction policy for resident cache entries.
+///
+/// Currently only CLOCK (second-chance) is implemented; the enum exists so
+/// alternative policies (e.g. strict LRU) can be added without changing the
+/// `CacheConfig` shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// CLOCK (second-chance) eviction driven by the `referenced` bit set on
+    /// every `get`/`page_text`/`page_image` access.
+    Clock,
+}
+
+/// Configuration for the global [`PdfCache`], installed once via
+/// [`install_config`] before the cache is first touched.
+///
+/// Modeled on pagecache-style `ConfigBuilder`s: a builder produces an
+/// immutable snapshot that [`cache()`] consults on first initialization.
+/// Setters like [`PdfCache::set_budget_bytes`] remain available for tuning
+/// an already-running cache at runtime.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    budget_bytes: usize,
+    /// Treat an entry as stale once it hasn't been accessed within this
+    /// duration, even if the backing file's mtime is unchanged: the next
+    /// lookup reloads it. Useful on network mounts where mtime resolution is
+    /// too coarse to catch external changes. Idle entries are not dropped on
+    /// a timer; they stay resident until looked up again or reclaimed by the
+    /// CLOCK evictor.
+    ttl: Option<Duration>,
+    /// Load PDFs via `mmap` (the default) rather than a plain `read`. Some
+    /// filesystems (notably certain network mounts) misbehave under mmap.
+    use_mmap: bool,
+    eviction_policy: EvictionPolicy,
+    text_compression: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            budget_bytes: DEFAULT_BUDGET_BYTES,
+            ttl: None,
+            use_mmap: true,
+            eviction_policy: EvictionPolicy::Clock,
+            text_compression: false,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Start building a `CacheConfig`, defaulting to the same behavior as
+    /// `PdfCache::new()`.
+    pub fn builder() -> CacheConfigBuilder {
+        CacheConfigBuilder::default()
+    }
+}
+
+/// Builder for [`CacheConfig`]. See module docs for the fields it controls.
+#[derive(Debug, Clone, Default)]
+pub struct CacheConfigBuilder(CacheConfig);
+
+impl CacheConfigBuilder {
+    /// Resident memory budget in bytes (default: 512 MiB).
+    pub fn budget_bytes(mut self, budget_bytes: usize) -> Self {
+        self.0.budget_bytes = budget_bytes;
+        self
+    }
+
+    /// Reload entries on their next lookup once they haven't been accessed
+    /// within `ttl`, independent of mtime checks. Idle entries are not
+    /// dropped on a timer.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.0.ttl = Some(ttl);
+        self
+    }
+
+    /// Whether to load PDFs via `mmap` (default `true`) or a plain `read`.
+    pub fn use_mmap(mut self, use_mmap: bool) -> Self {
+        self.0.use_mmap = use_mmap;
+        self
+    }
+
+    /// Eviction policy for resident entries (default: CLOCK).
+    pub fn eviction_policy(mut self, eviction_policy: EvictionPolicy) -> Self {
+        self.0.eviction_policy = eviction_policy;
+        self
+    }
+
+    /// Whether newly-cached page text is zstd-compressed (default `false`).
+    pub fn text_compression(mut self, enabled: bool) -> Self {
+        self.0.text_compression = enabled;
+        self
+    }
+
+    pub fn build(self) -> CacheConfig {
+        self.0
+    }
+}
+
+static CACHE_CONFIG: OnceLock<CacheConfig> = OnceLock::new();
+
+/// Install the configuration the global cache will initialize with.
+///
+/// Must be called before the first call to [`cache()`] (e.g. from the
+/// binary's startup path); once the cache has initialized, later calls have
+/// no effect on it. Returns the config back on failure if one was already
+/// installed.
+pub fn install_config(config: CacheConfig) -> Result<(), CacheConfig> {
+    CACHE_CONFIG.set(config)
+}
+
 // ==============================================================================
 // PDF Cache
 // ==============================================================================
@@ -75,17 +470,70 @@ fn extract_text_from_doc_page(
 ///
 /// Uses papaya's concurrent HashMap for lock-free reads and safe concurrent
 /// writes. Cache entries are keyed by canonical path to handle symlinks.
+///
+/// Resident memory is bounded by `budget_bytes`: when an insert (or a
+/// `page_text` extraction) pushes `total_bytes` over budget, a CLOCK
+/// (second-chance) evictor walks `resident` and reclaims space from entries
+/// that haven't been referenced since its last pass. `Arc` clones already
+/// held by callers remain valid after their entry is evicted - only the
+/// cache's own slot is dropped.
 pub struct PdfCache {
     cache: HashMap<PathBuf, CachedPdf>,
+    total_bytes: AtomicUsize,
+    budget_bytes: AtomicUsize,
+    /// CLOCK hand order: keys in the order they were (re)inserted or given a
+    /// second chance. A reload replaces its path's existing slot, so each key
+    /// appears at most once; the evictor skips any slot whose key it no
+    /// longer finds in `cache`.
+    resident: Mutex<VecDeque<PathBuf>>,
+    /// Whether pages inserted into `text_cache` should be zstd-compressed.
+    /// Shared (via `Arc`) with every `CachedPdf` so toggling this affects
+    /// pages inserted after the call, without needing to touch existing entries.
+    compression_enabled: Arc<AtomicBool>,
+    /// Reload an entry on its next `get` if it hasn't been accessed within
+    /// this duration, even when its mtime still matches. Idle entries are not
+    /// dropped on a timer. `None` disables TTL-based reloads.
+    ttl: Option<Duration>,
+    /// Load PDFs via `mmap` (the default) rather than a plain `read`.
+    use_mmap: bool,
+    /// Currently informational: only `EvictionPolicy::Clock` is implemented.
+    #[allow(dead_code)]
+    eviction_policy: EvictionPolicy,
 }
 
 impl PdfCache {
     fn new() -> Self {
+        Self::with_config(CacheConfig::default())
+    }
+
+    fn with_config(config: CacheConfig) -> Self {
         PdfCache {
             cache: HashMap::new(),
+            total_bytes: AtomicUsize::new(0),
+            budget_bytes: AtomicUsize::new(config.budget_bytes),
+            resident: Mutex::new(VecDeque::new()),
+            compression_enabled: Arc::new(AtomicBool::new(config.text_compression)),
+            ttl: config.ttl,
+            use_mmap: config.use_mmap,
+            eviction_policy: config.eviction_policy,
         }
     }
 
+    /// Set the memory budget (in bytes) for the cache, evicting immediately
+    /// if the new budget is below current usage.
+    pub fn set_budget_bytes(&self, budget_bytes: usize) {
+        self.budget_bytes.store(budget_bytes, Ordering::Relaxed);
+        self.evict_to_budget();
+    }
+
+    /// Toggle whether newly-cached page text is zstd-compressed. Off by
+    /// default; callers who'd rather trade latency for lower memory use can
+    /// turn it on. Pages already resident keep their existing representation
+    /// until re-extracted.
+    pub fn set_text_compression(&self, enabled: bool) {
+        self.compression_enabled.store(enabled, Ordering::Relaxed);
+    }
+
     /// Get or load a PDF from the cache.
     ///
     /// If the file is already cached and its mtime matches, returns the
@@ -107,17 +555,103 @@ impl PdfCache {
         // Try to get from cache first.
         let cache_guard = self.cache.pin();
         if let Some(cached) = cache_guard.get(&canonical) {
-            if cached.mtime == current_mtime {
+            let expired = self
+                .ttl
+                .is_some_and(|ttl| cached.last_accessed.lock().unwrap().elapsed() > ttl);
+            if cached.mtime == current_mtime && !expired {
+                cached.touch();
                 return Ok(cached.clone());
             }
-            // Stale entry - will be replaced below.
+            // Stale entry (mtime changed, or TTL expired) - will be replaced below.
         }
 
         // Load and cache the PDF.
-        let cached = load_pdf(&canonical, current_mtime)?;
-        cache_guard.insert(canonical, cached.clone());
+        let cached = load_pdf(
+            &canonical,
+            current_mtime,
+            Arc::clone(&self.compression_enabled),
+            self.use_mmap,
+        )?;
+        let size = cached.size.load(Ordering::Relaxed);
+
+        if let Some(old) = cache_guard.get(&canonical) {
+            // This reload supersedes `old`: clear its `live` flag so any
+            // clone a caller is still holding stops contributing further
+            // growth/shrink to `total_bytes` once it's no longer reachable
+            // through `cache`/`resident`.
+            old.live.store(false, Ordering::Relaxed);
+            self.total_bytes
+                .fetch_sub(old.size.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        cache_guard.insert(canonical.clone(), cached.clone());
+        self.total_bytes.fetch_add(size, Ordering::Relaxed);
+
+        // Drop any prior slot for this path before pushing the new one, so
+        // repeatedly reloading the same file (e.g. a network mount that's
+        // rewritten often) can't grow `resident` without bound even while
+        // `total_bytes` stays under budget.
+        let mut resident = self.resident.lock().unwrap();
+        resident.retain(|p| p != &canonical);
+        resident.push_back(canonical);
+        drop(resident);
+
+        // A fresh load is itself a "get" hit - mark it referenced so the
+        // CLOCK hand gives it a second chance and reclaims older unreferenced
+        // entries ahead of it. If the budget still can't be met once the hand
+        // has cleared its bit, this same pass evicts it too.
+        cached.touch();
+
+        self.evict_to_budget();
         Ok(cached)
     }
+
+    /// Account for growth of an already-resident entry (e.g. a newly
+    /// extracted page added to its `text_cache`), then evict if over budget.
+    fn account_growth(&self, additional_bytes: usize) {
+        self.total_bytes
+            .fetch_add(additional_bytes, Ordering::Relaxed);
+        self.evict_to_budget();
+    }
+
+    /// Account for shrinkage of an already-resident entry (e.g. `hot_text`
+    /// dropping a decompressed page), without needing to evict anything.
+    fn account_shrink(&self, bytes: usize) {
+        self.total_bytes.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// Walk the CLOCK hand, evicting unreferenced entries until resident
+    /// bytes are back under budget (or there's nothing left to evict).
+    fn evict_to_budget(&self) {
+        let mut resident = self.resident.lock().unwrap();
+        let cache_guard = self.cache.pin();
+
+        while self.total_bytes.load(Ordering::Relaxed) > self.budget_bytes.load(Ordering::Relaxed) {
+            let Some(key) = resident.pop_front() else {
+                break;
+            };
+
+            let Some(entry) = cache_guard.get(&key) else {
+                // No longer in the map; nothing to do for this slot.
+                continue;
+            };
+
+            if entry.referenced.swap(false, Ordering::Relaxed) {
+                // Give it a second chance: clear the flag and move it to the
+                // back of the clock.
+                resident.push_back(key);
+                continue;
+            }
+
+            let size = entry.size.load(Ordering::Relaxed);
+            // Clear `live` before dropping the entry's slot so a clone a
+            // caller is still holding (e.g. mid-`grep_pdf`) stops feeding
+            // further growth into `total_bytes` - there's no `resident` slot
+            // left for it to ever be reclaimed from.
+            entry.live.store(false, Ordering::Relaxed);
+            cache_guard.remove(&key);
+            self.total_bytes.fetch_sub(size, Ordering::Relaxed);
+        }
+    }
 }
 
 // ==============================================================================
@@ -127,8 +661,14 @@ impl PdfCache {
 static PDF_CACHE: OnceLock<PdfCache> = OnceLock::new();
 
 /// Get the global PDF cache instance.
+///
+/// Initializes from the config installed via [`install_config`], or
+/// `CacheConfig::default()` if none was installed before this first call.
 pub fn cache() -> &'static PdfCache {
-    PDF_CACHE.get_or_init(PdfCache::new)
+    PDF_CACHE.get_or_init(|| {
+        let config = CACHE_CONFIG.get_or_init(CacheConfig::default).clone();
+        PdfCache::with_config(config)
+    })
 }
 
 /// Convenience function to get a cached PDF from the global cache.
@@ -140,39 +680,63 @@ pub fn get_cached_pdf<P: AsRef<Path>>(path: P) -> Result<CachedPdf> {
 // PDF Loading
 // ==============================================================================
 
-/// Load a PDF file via memory mapping and parse it.
+/// Load a PDF file and parse it, via memory mapping or a plain read.
 ///
-/// We use mmap for efficient loading (the OS handles paging), but we don't
-/// store it afterward since lopdf's Document owns all its data independently
-/// after parsing.
-fn load_pdf(path: &Path, mtime: SystemTime) -> Result<CachedPdf> {
-    // Open the file for memory mapping.
-    let file = File::open(path).with_context(|| format!("open PDF file: {}", path.display()))?;
-
-    // Create a memory map of the file.
-    // SAFETY: The file is opened read-only, and we don't modify the underlying
-    // file while the map exists. The map is treated as immutable bytes.
-    let mmap = unsafe { Mmap::map(&file) }
-        .with_context(|| format!("memory-map PDF file: {}", path.display()))?;
-
-    // Hint to the OS that we'll access the file randomly (PDF parsing jumps
-    // around the file structure), so read-ahead would be wasteful.
-    #[cfg(unix)]
-    {
-        // Best-effort advisory; ignore errors since it's just an optimization hint.
-        let _ = mmap.advise(memmap2::Advice::Random);
-    }
-
-    // Parse the document from the memory-mapped bytes.
-    // After this, the Document owns all its data - we don't need the mmap anymore.
-    let doc =
-        Document::load_mem(&mmap).with_context(|| format!("parse PDF: {}", path.display()))?;
+/// Memory mapping (the default, `use_mmap: true`) lets the OS handle paging
+/// and avoids a full upfront copy, but some filesystems (notably certain
+/// network mounts) misbehave under mmap; `use_mmap: false` falls back to a
+/// plain `read`. Either way we don't keep the bytes around afterward since
+/// lopdf's `Document` owns all its data independently after parsing.
+fn load_pdf(
+    path: &Path,
+    mtime: SystemTime,
+    compression_enabled: Arc<AtomicBool>,
+    use_mmap: bool,
+) -> Result<CachedPdf> {
+    // The byte length is a proxy for the parsed Document's footprint - we
+    // don't have a cheap way to measure lopdf's actual heap usage.
+    let (doc, byte_len) = if use_mmap {
+        let file =
+            File::open(path).with_context(|| format!("open PDF file: {}", path.display()))?;
+
+        // Create a memory map of the file.
+        // SAFETY: The file is opened read-only, and we don't modify the underlying
+        // file while the map exists. The map is treated as immutable bytes.
+        let mmap = unsafe { Mmap::map(&file) }
+            .with_context(|| format!("memory-map PDF file: {}", path.display()))?;
+
+        // Hint to the OS that we'll access the file randomly (PDF parsing jumps
+        // around the file structure), so read-ahead would be wasteful.
+        #[cfg(unix)]
+        {
+            // Best-effort advisory; ignore errors since it's just an optimization hint.
+            let _ = mmap.advise(memmap2::Advice::Random);
+        }
+
+        let doc = Document::load_mem(&mmap)
+            .with_context(|| format!("parse PDF: {}", path.display()))?;
+        (doc, mmap.len())
+    } else {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("read PDF file: {}", path.display()))?;
+        let doc = Document::load_mem(&bytes)
+            .with_context(|| format!("parse PDF: {}", path.display()))?;
+        (doc, bytes.len())
+    };
     let doc = Arc::new(doc);
 
     Ok(CachedPdf {
         doc,
+        path: Arc::new(path.to_path_buf()),
         mtime,
         text_cache: Arc::new(HashMap::new()),
+        hot_text: Arc::new(Mutex::new(VecDeque::with_capacity(HOT_TEXT_CAPACITY))),
+        compression_enabled,
+        raster_cache: Arc::new(HashMap::new()),
+        size: Arc::new(AtomicUsize::new(byte_len)),
+        referenced: Arc::new(AtomicBool::new(false)),
+        last_accessed: Arc::new(Mutex::new(Instant::now())),
+        live: Arc::new(AtomicBool::new(true)),
     })
 }
 
@@ -261,6 +825,92 @@ mod tests {
         std::fs::remove_file(&pdf_path).ok();
     }
 
+    #[test]
+    fn eviction_reclaims_unreferenced_entries_under_budget() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_eviction.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        // Use a private cache instance (not the global singleton) so this
+        // test's tiny budget can't starve unrelated tests sharing `cache()`.
+        let local = PdfCache::new();
+        let cached = local.get(&pdf_path).expect("first get");
+        let size = cached.size.load(Ordering::Relaxed);
+        assert!(size > 0, "cached entry should have a non-zero size");
+
+        // Clear the reference flag set by `get`, then shrink the budget
+        // below the entry's size so the evictor reclaims it on its next pass.
+        cached.referenced.store(false, Ordering::Relaxed);
+        local.set_budget_bytes(size - 1);
+
+        assert_eq!(
+            local.total_bytes.load(Ordering::Relaxed),
+            0,
+            "entry should have been evicted"
+        );
+        assert!(
+            local
+                .cache
+                .pin()
+                .get(&pdf_path.canonicalize().unwrap())
+                .is_none(),
+            "evicted entry should no longer be in the map"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn freshly_loaded_entry_survives_the_next_eviction_pass() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let older_path = dir.join("test_fresh_load_older.pdf");
+        let pdf_path = dir.join("test_fresh_load_survives.pdf");
+        create_minimal_pdf(&older_path);
+        create_minimal_pdf(&pdf_path);
+
+        // Load an idle older entry (reference bit cleared), then set a budget
+        // that fits one entry but not two. Both files hold the same minimal
+        // PDF, so both entries have the same size.
+        let local = PdfCache::new();
+        let older = local.get(&older_path).expect("get older entry");
+        let size = older.size.load(Ordering::Relaxed);
+        older.referenced.store(false, Ordering::Relaxed);
+        local.set_budget_bytes(2 * size - 1);
+
+        // Loading a second entry pushes the cache over budget, so the
+        // `evict_to_budget()` pass run during this load must reclaim one of
+        // the two: the idle older entry goes, the fresh load stays.
+        let cached = local.get(&pdf_path).expect("get into an over-budget cache");
+
+        assert!(
+            cached.referenced.load(Ordering::Relaxed),
+            "a fresh load is itself a get-hit and should be marked referenced"
+        );
+        assert!(
+            local
+                .cache
+                .pin()
+                .get(&pdf_path.canonicalize().unwrap())
+                .is_some(),
+            "entry should survive the eviction pass run during its own load"
+        );
+        assert!(
+            local
+                .cache
+                .pin()
+                .get(&older_path.canonicalize().unwrap())
+                .is_none(),
+            "the idle older entry should be the one reclaimed"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&older_path).ok();
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
     #[test]
     fn symlink_and_real_path_return_same_arcs() {
         let dir = std::env::temp_dir().join("inpdf_cache_test");
@@ -291,4 +941,247 @@ mod tests {
 
         std::fs::remove_file(&pdf_path).ok();
     }
+
+    #[test]
+    fn config_builder_overrides_defaults() {
+        let config = CacheConfig::builder()
+            .budget_bytes(1024)
+            .ttl(Duration::from_secs(30))
+            .use_mmap(false)
+            .eviction_policy(EvictionPolicy::Clock)
+            .text_compression(true)
+            .build();
+
+        assert_eq!(config.budget_bytes, 1024);
+        assert_eq!(config.ttl, Some(Duration::from_secs(30)));
+        assert!(!config.use_mmap);
+        assert!(config.text_compression);
+    }
+
+    #[test]
+    fn ttl_expiry_reloads_entry_even_with_unchanged_mtime() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_ttl.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        // Use a private cache instance (not the global singleton) with a
+        // tiny TTL so this test doesn't have to wait long.
+        let local = PdfCache::with_config(
+            CacheConfig::builder()
+                .ttl(Duration::from_millis(10))
+                .build(),
+        );
+        let cached1 = local.get(&pdf_path).expect("first get");
+
+        std::thread::sleep(Duration::from_millis(20));
+
+        let cached2 = local.get(&pdf_path).expect("second get after TTL expiry");
+
+        assert!(
+            !Arc::ptr_eq(&cached1.doc, &cached2.doc),
+            "doc Arcs should differ once the TTL has expired, even with the same mtime"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn hot_text_extra_bytes_are_tracked_and_released_on_eviction() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_hot_bytes.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        let cached = get_cached_pdf(&pdf_path).expect("get");
+        let size_before = cached.size.load(Ordering::Relaxed);
+
+        // Simulate a compressed-mode hot_text insert, which holds a
+        // decompressed copy `hot_extra_bytes` larger than what `text_cache`
+        // already counts for the same page (the compressed blob).
+        cached.hot_text_insert(1, Arc::new("x".repeat(1000)), 1000);
+        assert_eq!(
+            cached.size.load(Ordering::Relaxed),
+            size_before + 1000,
+            "a hot_text insert's extra bytes should be added to the entry's size"
+        );
+
+        // Fill the hot set past capacity so the page-1 entry above falls out
+        // of `hot_text`; its extra bytes should be released back out of size.
+        for page in 2..=(HOT_TEXT_CAPACITY as u32 + 1) {
+            cached.hot_text_insert(page, Arc::new(String::new()), 0);
+        }
+        assert_eq!(
+            cached.size.load(Ordering::Relaxed),
+            size_before,
+            "evicting the hot_text entry should release its extra bytes"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn evicted_entry_growth_does_not_inflate_total_bytes() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_orphan_growth.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        let local = PdfCache::new();
+        let cached = local.get(&pdf_path).expect("first get");
+
+        // Force eviction the same way `eviction_reclaims_unreferenced_entries_under_budget`
+        // does, leaving `cached` as an orphaned clone no longer registered in
+        // `local.cache`/`local.resident`.
+        let size = cached.size.load(Ordering::Relaxed);
+        cached.referenced.store(false, Ordering::Relaxed);
+        local.set_budget_bytes(size - 1);
+        assert_eq!(
+            local.total_bytes.load(Ordering::Relaxed),
+            0,
+            "entry should have been evicted before the orphaned growth below"
+        );
+        assert!(
+            !cached.live.load(Ordering::Relaxed),
+            "eviction should clear the entry's live flag"
+        );
+
+        // Further growth on the orphaned clone (e.g. a caller mid-`grep_pdf`
+        // extracting more pages) must stay local to the clone and never reach
+        // the cache's global budget again - there's no `resident` slot left
+        // to ever reclaim it from.
+        cached.account_growth(1_000_000);
+        assert_eq!(
+            local.total_bytes.load(Ordering::Relaxed),
+            0,
+            "growth on an orphaned entry must not inflate total_bytes"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn reload_supersedes_old_entry_and_dedupes_resident_slot() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_reload_dedupe.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        let local = PdfCache::new();
+        let old = local.get(&pdf_path).expect("first get");
+        assert!(old.live.load(Ordering::Relaxed));
+
+        // Touch the file's mtime forward so the next `get` treats it as
+        // stale and reloads it, without changing `resident`'s length if
+        // dedupe is working.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        create_minimal_pdf(&pdf_path);
+        let _new = local.get(&pdf_path).expect("reload after mtime change");
+
+        assert!(
+            !old.live.load(Ordering::Relaxed),
+            "a reload should clear the superseded entry's live flag"
+        );
+        assert_eq!(
+            local.resident.lock().unwrap().len(),
+            1,
+            "reloading the same path should replace its resident slot, not add another"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn reinserting_the_same_page_image_does_not_double_count_its_bytes() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_image_dedupe.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        let cached = get_cached_pdf(&pdf_path).expect("get");
+        let size_before = cached.size.load(Ordering::Relaxed);
+
+        let image = Arc::new(crate::pdf::render::PageImage {
+            page: 1,
+            width: 10,
+            height: 10,
+            png: vec![0u8; 1000],
+        });
+
+        // `render_pages` collects every cache-miss page before rendering or
+        // inserting any of them, so a page requested twice in one batch
+        // inserts the same `(page, dpi)` key twice with an equal-sized image.
+        cached.insert_page_image(1, 72.0, Arc::clone(&image));
+        cached.insert_page_image(1, 72.0, Arc::clone(&image));
+
+        assert_eq!(
+            cached.size.load(Ordering::Relaxed),
+            size_before + 1000,
+            "reinserting an already-resident (page, dpi) must not double-count its bytes"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn repeated_text_cache_insert_for_the_same_page_does_not_double_count_bytes() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_text_dedupe.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        let cached = get_cached_pdf(&pdf_path).expect("get");
+        let size_before = cached.size.load(Ordering::Relaxed);
+
+        // Simulate two racing `page_text` misses for the same page both
+        // reaching the `text_cache` insert - the way concurrent
+        // `extract_text_pages` fan-out over duplicate page numbers would -
+        // mirroring `page_text`'s own insert-then-maybe-account sequence.
+        let entry = CachedText::Plain(Arc::new("x".repeat(500)));
+        let size = entry.resident_size();
+        let guard = cached.text_cache.pin();
+        if guard.insert(1, entry.clone()).is_none() {
+            cached.account_growth(size);
+        }
+        if guard.insert(1, entry).is_none() {
+            cached.account_growth(size);
+        }
+        drop(guard);
+
+        assert_eq!(
+            cached.size.load(Ordering::Relaxed),
+            size_before + size,
+            "only the insert that finds the page vacant should account its growth"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
+
+    #[test]
+    fn page_image_rejects_out_of_range_page_numbers() {
+        let dir = std::env::temp_dir().join("inpdf_cache_test");
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let pdf_path = dir.join("test_page_image_bounds.pdf");
+        create_minimal_pdf(&pdf_path);
+
+        let cached = get_cached_pdf(&pdf_path).expect("get");
+
+        assert!(
+            cached.page_image(0, 72.0).is_err(),
+            "page 0 is out of range and should error rather than underflow/panic"
+        );
+        assert!(
+            cached.page_image(2, 72.0).is_err(),
+            "page 2 is out of range for this 1-page PDF and should error"
+        );
+
+        // Cleanup.
+        std::fs::remove_file(&pdf_path).ok();
+    }
 }
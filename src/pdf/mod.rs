@@ -1,9 +1,10 @@
 pub mod cache;
 pub mod document;
 pub mod page_labels;
+pub mod render;
 pub mod text;
 pub mod toc;
 
 #[allow(unused_imports)]
-pub use cache::{get_cached_pdf, CachedPdf};
+pub use cache::{get_cached_pdf, install_config, CacheConfig, CachedPdf, EvictionPolicy};
 pub use document::PdfDocument;
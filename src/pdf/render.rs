@@ -0,0 +1,153 @@
+//! Page rasterization: render PDF pages to PNG images.
+//!
+//! Mirrors ripgrep-all's `pdfpages` adapter, which converts a PDF into
+//! per-page PNGs so downstream tools (OCR, image search, visual inspection)
+//! can work with content that plain-text extraction can't surface, such as
+//! scanned pages or figures.
+
+use crate::pdf::cache::get_cached_pdf;
+use anyhow::{Context, Result};
+use pdfium_render::prelude::*;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Pdfium's C library is not safe to drive from multiple threads at once
+/// without external synchronization, so every render in this module goes
+/// through this single bound instance, serialized by the `Mutex`. It's
+/// bound lazily on first use rather than at startup, since binding can fail
+/// (e.g. no system pdfium library) and we'd rather surface that from the
+/// first render call than from process init.
+static PDFIUM: OnceLock<Mutex<Pdfium>> = OnceLock::new();
+
+/// Get the shared pdfium binding, binding to the system library on first
+/// call.
+fn pdfium_binding() -> Result<&'static Mutex<Pdfium>> {
+    if let Some(existing) = PDFIUM.get() {
+        return Ok(existing);
+    }
+    let bindings = Pdfium::bind_to_system_library().context("bind to system pdfium library")?;
+    Ok(PDFIUM.get_or_init(|| Mutex::new(Pdfium::new(bindings))))
+}
+
+/// Render specific pages of a PDF to PNG images at the given resolution.
+///
+/// Pages are 1-indexed. Routes through the global PDF cache so the parsed
+/// document (and its mtime-based invalidation) is shared with text/grep
+/// operations, and renders are cached per `(page, dpi)` for the life of the
+/// cache entry. Cache-miss pages are rendered through a single pdfium
+/// `Document` open for this call (rather than one open per page), since
+/// reopening and reparsing the whole PDF through pdfium per page would
+/// otherwise dominate the cost of rendering a multi-page request.
+pub fn render_pages<P: AsRef<Path>>(path: P, pages: &[u32], dpi: f32) -> Result<Vec<PageImage>> {
+    let path = path.as_ref();
+    let cached = get_cached_pdf(path).with_context(|| format!("cache PDF: {}", path.display()))?;
+    let total_pages = cached.document().get_pages().len() as u32;
+
+    // Validate page numbers.
+    for &page in pages {
+        if page == 0 || page > total_pages {
+            anyhow::bail!("Page {} is out of range (1-{})", page, total_pages);
+        }
+    }
+
+    // Serve whatever's already resident in the raster cache, and collect
+    // the rest so they can share a single pdfium document open below.
+    let mut images: Vec<Option<Arc<PageImage>>> = Vec::with_capacity(pages.len());
+    let mut pending = Vec::new();
+    for (idx, &page_num) in pages.iter().enumerate() {
+        match cached.cached_page_image(page_num, dpi) {
+            Some(image) => images.push(Some(image)),
+            None => {
+                images.push(None);
+                pending.push((idx, page_num));
+            }
+        }
+    }
+
+    if !pending.is_empty() {
+        let pdfium = pdfium_binding()?
+            .lock()
+            .expect("pdfium binding mutex should not be poisoned");
+
+        let document = pdfium
+            .load_pdf_from_file(path, None)
+            .with_context(|| format!("open PDF for rendering: {}", path.display()))?;
+
+        for (idx, page_num) in pending {
+            let image = Arc::new(
+                render_page_from_document(&document, page_num, dpi)
+                    .with_context(|| format!("render page {}", page_num))?,
+            );
+            cached.insert_page_image(page_num, dpi, Arc::clone(&image));
+            images[idx] = Some(image);
+        }
+    }
+
+    Ok(images
+        .into_iter()
+        .map(|image| (*image.expect("every page slot should have been filled")).clone())
+        .collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct PageImage {
+    pub page: u32,
+    pub width: u32,
+    pub height: u32,
+    pub png: Vec<u8>,
+}
+
+/// Render a single page of the PDF at `path` to a PNG image.
+///
+/// This reopens the file through pdfium rather than reusing the cached
+/// `lopdf::Document`, since lopdf only models the object graph and has no
+/// rendering support. Used for one-off renders (e.g. `CachedPdf::page_image`
+/// on a cache miss); `render_pages` instead opens the document once and
+/// calls `render_page_from_document` directly for each page it needs.
+pub(crate) fn render_page(path: &Path, page_num: u32, dpi: f32) -> Result<PageImage> {
+    let pdfium = pdfium_binding()?
+        .lock()
+        .expect("pdfium binding mutex should not be poisoned");
+
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .with_context(|| format!("open PDF for rendering: {}", path.display()))?;
+
+    render_page_from_document(&document, page_num, dpi)
+}
+
+/// Render a single page from an already-open pdfium document.
+fn render_page_from_document(
+    document: &PdfDocument<'_>,
+    page_num: u32,
+    dpi: f32,
+) -> Result<PageImage> {
+    let page = document
+        .pages()
+        .get((page_num - 1) as u16)
+        .with_context(|| format!("get page {} for rendering", page_num))?;
+
+    // PDF page dimensions are in points (1/72 inch); scale to the requested DPI.
+    let scale = dpi / 72.0;
+    let render_config = PdfRenderConfig::new().scale_page_by_factor(scale);
+
+    let bitmap = page
+        .render_with_config(&render_config)
+        .with_context(|| format!("rasterize page {}", page_num))?;
+
+    let width = bitmap.width() as u32;
+    let height = bitmap.height() as u32;
+
+    let mut png = Vec::new();
+    bitmap
+        .as_image()
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .with_context(|| format!("encode page {} as PNG", page_num))?;
+
+    Ok(PageImage {
+        page: page_num,
+        width,
+        height,
+        png,
+    })
+}
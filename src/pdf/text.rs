@@ -1,11 +1,21 @@
-use crate::pdf::cache::get_cached_pdf;
+use crate::pdf::cache::{get_cached_pdf, CachedPdf};
 use anyhow::{Context, Result};
+use rayon::prelude::*;
 use std::path::Path;
+use std::sync::Arc;
+
+/// At or below this many pages, extraction runs sequentially on the calling
+/// thread - spinning up rayon's thread pool doesn't pay for itself on a
+/// handful of pages.
+const PARALLEL_PAGE_THRESHOLD: usize = 32;
 
 /// Extract text from specific pages of a PDF.
 ///
 /// Uses the per-page text cache to avoid re-extracting text that was
-/// already processed (e.g., by a previous grep operation).
+/// already processed (e.g., by a previous grep operation). Pages are
+/// extracted in parallel via rayon once the requested page count crosses
+/// `PARALLEL_PAGE_THRESHOLD`, since `text_cache` is a lock-free concurrent
+/// map and safe to insert into from multiple threads at once.
 pub fn extract_text_pages<P: AsRef<Path>>(path: P, pages: &[u32]) -> Result<Vec<PageText>> {
     let path = path.as_ref();
     let cached = get_cached_pdf(path).with_context(|| format!("cache PDF: {}", path.display()))?;
@@ -18,20 +28,21 @@ pub fn extract_text_pages<P: AsRef<Path>>(path: P, pages: &[u32]) -> Result<Vec<
         }
     }
 
-    let mut results = Vec::new();
-
-    // Extract text for each requested page using the text cache.
-    for &page_num in pages {
+    let extract_one = |&page_num: &u32| -> Result<PageText> {
         let text = cached
             .page_text(page_num)
             .with_context(|| format!("extract text from page {}", page_num))?;
-        results.push(PageText {
+        Ok(PageText {
             page: page_num,
             text: text.to_string(),
-        });
-    }
+        })
+    };
 
-    Ok(results)
+    if pages.len() > PARALLEL_PAGE_THRESHOLD {
+        pages.par_iter().map(extract_one).collect()
+    } else {
+        pages.iter().map(extract_one).collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -40,49 +51,85 @@ pub struct PageText {
     pub text: String,
 }
 
-/// Search for a pattern in PDF text, returning matches with page numbers and context.
+/// Search for a pattern in PDF text, returning matches grouped with
+/// ripgrep-style context lines.
 ///
 /// Uses the per-page text cache to avoid re-extracting text. This benefits MCP
 /// sessions where multiple grep operations may search the same PDF, or where
 /// grep is followed by reading specific pages.
+///
+/// Pages are searched in parallel via rayon once the document crosses
+/// `PARALLEL_PAGE_THRESHOLD` pages - `text_cache` is a lock-free concurrent
+/// map, so extraction fan-out is safe even though it means every page gets
+/// extracted up front rather than stopping as soon as `max_results` is hit.
+/// Matches are sorted by `(page, line_number)` and truncated to
+/// `max_results` before grouping, so results are deterministic regardless of
+/// which page finished extracting first.
+///
+/// `context` is the number of lines of surrounding text (like ripgrep's
+/// `-C`) to attach to each match. Matches on the same page whose context
+/// windows overlap or touch are merged into a single [`GrepGroup`] with a
+/// contiguous `line_range`, rather than duplicating the shared lines across
+/// separate hits.
 pub fn grep_pdf<P: AsRef<Path>>(
     path: P,
     pattern: &regex::Regex,
     max_results: usize,
-) -> Result<Vec<GrepMatch>> {
+    context: usize,
+) -> Result<Vec<GrepGroup>> {
     let path = path.as_ref();
     let cached = get_cached_pdf(path).with_context(|| format!("cache PDF: {}", path.display()))?;
     let total_pages = cached.document().get_pages().len() as u32;
 
-    let mut matches = Vec::new();
+    let pages: Vec<u32> = (1..=total_pages).collect();
+    let find_on_page = |&page_num: &u32| matches_on_page(&cached, page_num, pattern);
 
-    // Extract and search each page individually for correct page attribution.
-    // Text is cached per-page, so subsequent searches or reads are cheap.
-    for page_num in 1..=total_pages {
-        let page_text = match cached.page_text(page_num) {
-            Ok(text) => text,
-            Err(_) => continue, // Skip pages that fail to extract
-        };
-
-        for (line_idx, line) in page_text.lines().enumerate() {
-            let line_number = line_idx as u32 + 1;
-            for mat in pattern.find_iter(line) {
-                matches.push(GrepMatch {
-                    page: page_num,
-                    line_number,
-                    text: line.to_string(),
-                    match_start: mat.start() as u32,
-                    match_end: mat.end() as u32,
-                });
-
-                if matches.len() >= max_results {
-                    return Ok(matches);
-                }
+    let mut matches: Vec<GrepMatch> = if pages.len() > PARALLEL_PAGE_THRESHOLD {
+        pages.par_iter().flat_map(find_on_page).collect()
+    } else {
+        // Small PDFs (the common case) stay on the calling thread and keep
+        // the baseline's early exit: stop extracting further pages as soon
+        // as we've collected enough matches, rather than walking the whole
+        // document just to truncate afterward.
+        let mut matches = Vec::new();
+        for &page_num in &pages {
+            matches.extend(matches_on_page(&cached, page_num, pattern));
+            if matches.len() >= max_results {
+                break;
             }
         }
-    }
+        matches
+    };
+
+    matches.sort_by_key(|m| (m.page, m.line_number));
+    matches.truncate(max_results);
+
+    group_matches(&cached, matches, context)
+}
 
-    Ok(matches)
+/// Search a single page for `pattern` (extracting and caching its text on a
+/// cache miss), returning every match on that page. Pages that fail to
+/// extract contribute no matches.
+fn matches_on_page(cached: &CachedPdf, page_num: u32, pattern: &regex::Regex) -> Vec<GrepMatch> {
+    let page_text = match cached.page_text(page_num) {
+        Ok(text) => text,
+        Err(_) => return Vec::new(), // Skip pages that fail to extract
+    };
+
+    let mut matches = Vec::new();
+    for (line_idx, line) in page_text.lines().enumerate() {
+        let line_number = line_idx as u32 + 1;
+        for mat in pattern.find_iter(line) {
+            matches.push(GrepMatch {
+                page: page_num,
+                line_number,
+                text: line.to_string(),
+                match_start: mat.start() as u32,
+                match_end: mat.end() as u32,
+            });
+        }
+    }
+    matches
 }
 
 #[derive(Debug, Clone)]
@@ -93,3 +140,192 @@ pub struct GrepMatch {
     pub match_start: u32,
     pub match_end: u32,
 }
+
+/// One or more matches on the same page whose context windows overlap,
+/// merged into a single contiguous snippet.
+///
+/// `before`/`after` hold only the context lines outside the group's
+/// earliest and latest matches; lines strictly between two merged matches
+/// aren't duplicated here since they're already implied by `line_range` and
+/// can be re-read cheaply from the (already cached) page text if needed.
+#[derive(Debug, Clone)]
+pub struct GrepGroup {
+    pub page: u32,
+    /// Inclusive 1-indexed line range covered by this group, including
+    /// context lines.
+    pub line_range: (u32, u32),
+    pub before: Vec<String>,
+    pub matches: Vec<GrepMatch>,
+    pub after: Vec<String>,
+}
+
+/// Attach `context` lines of before/after text to each match and merge
+/// matches on the same page whose context windows overlap or touch.
+///
+/// `matches` must already be sorted by `(page, line_number)`.
+fn group_matches(
+    cached: &CachedPdf,
+    matches: Vec<GrepMatch>,
+    context: usize,
+) -> Result<Vec<GrepGroup>> {
+    group_matches_with(matches, context, |page| {
+        cached
+            .page_text(page)
+            .with_context(|| format!("extract text from page {} for context", page))
+    })
+}
+
+/// Core of [`group_matches`], parameterized over how a page's text is
+/// fetched so the window-merging/clamping logic can be unit tested without a
+/// real `CachedPdf`.
+fn group_matches_with(
+    matches: Vec<GrepMatch>,
+    context: usize,
+    mut page_text: impl FnMut(u32) -> Result<Arc<String>>,
+) -> Result<Vec<GrepGroup>> {
+    let context = context as u32;
+    let mut groups: Vec<GrepGroup> = Vec::new();
+
+    for m in matches {
+        let window_start = m.line_number.saturating_sub(context).max(1);
+        let window_end = m.line_number + context;
+
+        if let Some(last) = groups.last_mut() {
+            if last.page == m.page && window_start <= last.line_range.1 + 1 {
+                last.line_range.1 = last.line_range.1.max(window_end);
+                last.matches.push(m);
+                continue;
+            }
+        }
+
+        groups.push(GrepGroup {
+            page: m.page,
+            line_range: (window_start, window_end),
+            before: Vec::new(),
+            matches: vec![m],
+            after: Vec::new(),
+        });
+    }
+
+    // Now that each group's final (possibly widened-by-merge) line_range is
+    // known, fill in before/after context straight from the page text.
+    for group in &mut groups {
+        let text = page_text(group.page)?;
+        let lines: Vec<&str> = text.lines().collect();
+        group.line_range.1 = group.line_range.1.min(lines.len() as u32);
+
+        let first_match_line = group.matches[0].line_number;
+        let last_match_line = group.matches[group.matches.len() - 1].line_number;
+
+        group.before = (group.line_range.0..first_match_line)
+            .filter_map(|line| lines.get((line - 1) as usize).map(|s| s.to_string()))
+            .collect();
+        group.after = (last_match_line + 1..=group.line_range.1)
+            .filter_map(|line| lines.get((line - 1) as usize).map(|s| s.to_string()))
+            .collect();
+    }
+
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a `GrepMatch` at `line` on `page`, with placeholder text/span
+    /// fields that `group_matches_with` doesn't consult.
+    fn grep_match(page: u32, line: u32) -> GrepMatch {
+        GrepMatch {
+            page,
+            line_number: line,
+            text: format!("line {}", line),
+            match_start: 0,
+            match_end: 1,
+        }
+    }
+
+    /// A `page_text` stand-in that returns `line_count` numbered lines for
+    /// every page, regardless of which page is asked for.
+    fn numbered_lines(line_count: u32) -> impl FnMut(u32) -> Result<Arc<String>> {
+        let text: String = (1..=line_count)
+            .map(|n| format!("line {}", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text = Arc::new(text);
+        move |_page| Ok(Arc::clone(&text))
+    }
+
+    #[test]
+    fn single_match_gets_symmetric_context() {
+        let groups =
+            group_matches_with(vec![grep_match(1, 10)], 2, numbered_lines(20)).expect("group");
+
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.line_range, (8, 12));
+        assert_eq!(group.before, vec!["line 8", "line 9"]);
+        assert_eq!(group.matches.len(), 1);
+        assert_eq!(group.after, vec!["line 11", "line 12"]);
+    }
+
+    #[test]
+    fn overlapping_windows_on_the_same_page_merge_into_one_group() {
+        // Context 3 around lines 10 and 14 covers (7,13) and (11,17), which
+        // overlap (11 <= 13 + 1), so both matches should land in one group.
+        let groups = group_matches_with(
+            vec![grep_match(1, 10), grep_match(1, 14)],
+            3,
+            numbered_lines(30),
+        )
+        .expect("group");
+
+        assert_eq!(groups.len(), 1, "overlapping windows should merge");
+        let group = &groups[0];
+        assert_eq!(group.line_range, (7, 17));
+        assert_eq!(group.matches.len(), 2);
+        assert_eq!(group.before, vec!["line 7", "line 8", "line 9"]);
+        assert_eq!(group.after, vec!["line 15", "line 16", "line 17"]);
+    }
+
+    #[test]
+    fn distant_matches_on_the_same_page_stay_separate() {
+        // Context 2 around lines 5 and 50 covers (3,7) and (48,52), nowhere
+        // near each other, so each match should get its own group.
+        let groups = group_matches_with(
+            vec![grep_match(1, 5), grep_match(1, 50)],
+            2,
+            numbered_lines(60),
+        )
+        .expect("group");
+
+        assert_eq!(groups.len(), 2, "far-apart matches should not merge");
+        assert_eq!(groups[0].line_range, (3, 7));
+        assert_eq!(groups[1].line_range, (48, 52));
+    }
+
+    #[test]
+    fn context_window_clamps_at_the_page_boundary_without_panicking() {
+        // Context 5 around line 2 would want lines (-3..=7); around line 19
+        // of a 20-line page it would want up to line 24. Both should clamp
+        // instead of underflowing/panicking or indexing past the last line.
+        let groups = group_matches_with(
+            vec![grep_match(1, 2), grep_match(1, 19)],
+            5,
+            numbered_lines(20),
+        )
+        .expect("group");
+
+        assert_eq!(groups.len(), 2);
+
+        let start_group = &groups[0];
+        assert_eq!(start_group.line_range.0, 1, "window_start clamps to line 1");
+        assert_eq!(start_group.before, vec!["line 1"]);
+
+        let end_group = &groups[1];
+        assert_eq!(
+            end_group.line_range.1, 20,
+            "window_end clamps to the page's last line"
+        );
+        assert_eq!(end_group.after, vec!["line 20"]);
+    }
+}